@@ -31,6 +31,24 @@ pub fn get_mod_max_state() -> u32 {
     MOD_MAX_STATE.load(Ordering::Relaxed)
 }
 
+/// How many state IDs to reserve per registered block kind when we don't
+/// know its actual property count (i.e. when extending the range from
+/// registry data alone, rather than from parsed block definitions).
+///
+/// This deliberately over-estimates: vanilla blocks average well under this
+/// many states each, but under-estimating would silently drop valid modded
+/// states, while over-estimating only costs a slightly larger `HashSet` when
+/// a caller converts a range into [`BlockStates`].
+const RESERVED_STATES_PER_BLOCK_KIND: u32 = 32;
+
+/// Computes a safe `set_mod_max_state` value from how many entries a
+/// block-related registry reported, for callers that only have the raw
+/// registry entry count (e.g. a `registry_data` handler) and not the actual
+/// block property definitions.
+pub fn mod_max_state_for_entry_count(entry_count: usize) -> u32 {
+    (entry_count as u32).saturating_mul(RESERVED_STATES_PER_BLOCK_KIND)
+}
+
 #[derive(Clone, Debug)]
 pub struct BlockStates {
     pub set: HashSet<BlockState>,
@@ -115,3 +133,25 @@ impl From<&LazyLock<RegistryTag<BlockKind>>> for BlockStates {
         Self::from(&**tag)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_max_state_for_entry_count_zero() {
+        assert_eq!(mod_max_state_for_entry_count(0), 0);
+    }
+
+    #[test]
+    fn test_mod_max_state_for_entry_count_saturates_instead_of_overflowing() {
+        // 200_000_000 * 32 overflows u32 (> 4_294_967_295); this must
+        // saturate to u32::MAX instead of wrapping or panicking
+        assert_eq!(mod_max_state_for_entry_count(200_000_000), u32::MAX);
+    }
+
+    #[test]
+    fn test_mod_max_state_for_entry_count_scales_linearly_below_the_overflow_point() {
+        assert_eq!(mod_max_state_for_entry_count(10), 320);
+    }
+}
@@ -3,7 +3,7 @@
 //! This module provides decoders for common modded Minecraft packet formats
 //! that the bot receives but doesn't fully implement.
 
-use std::io::Cursor;
+use std::{borrow::Cow, io::Cursor};
 
 // ---------------------------------------------------------------------------
 // VarInt helpers
@@ -34,6 +34,57 @@ pub fn read_varint_u32(cursor: &mut Cursor<&[u8]>) -> Option<u32> {
     read_varint(cursor).map(|v| v as u32)
 }
 
+/// Writes a Minecraft-style VarInt, the inverse of [`read_varint`].
+pub fn write_varint(out: &mut Vec<u8>, mut value: i32) {
+    loop {
+        let mut byte = (value & 0x7F) as u8;
+        value = ((value as u32) >> 7) as i32;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Writes a VarInt-prefixed string, the inverse of [`read_string`].
+///
+/// `read_string` decodes its bytes as Modified UTF-8, so this encodes the
+/// same way (via [`encode_modified_utf8`]) instead of plain UTF-8, or a
+/// string with an embedded NUL or an astral-plane character wouldn't
+/// round-trip.
+pub fn write_varint_prefixed_string(out: &mut Vec<u8>, s: &str) {
+    let bytes = encode_modified_utf8(s);
+    write_varint(out, bytes.len() as i32);
+    out.extend_from_slice(&bytes);
+}
+
+/// Reads a "VarShort", the length prefix FML uses on its custom-payload
+/// channels (notably the handshake's `ModList`) instead of a plain `i16`.
+///
+/// A plain short overflows and causes an OOM/garbage read once a modpack's
+/// mod list gets large, so FML packs the low 15 bits of the value into the
+/// first two bytes, then reads a continuation byte carrying the high bits
+/// if the top bit of those two bytes is set.
+pub fn read_varshort(cursor: &mut Cursor<&[u8]>) -> Option<i32> {
+    use std::io::Read;
+
+    let mut low_bytes = [0u8; 2];
+    cursor.read_exact(&mut low_bytes).ok()?;
+    let low = u16::from_be_bytes(low_bytes) as i32;
+    let low_value = low & 0x7FFF;
+
+    if low & 0x8000 != 0 {
+        let mut high_byte = [0u8; 1];
+        cursor.read_exact(&mut high_byte).ok()?;
+        Some(low_value | ((high_byte[0] as i32) << 15))
+    } else {
+        Some(low_value)
+    }
+}
+
 /// Reads a Minecraft-style prefixed UTF-8 string (VarInt length + bytes).
 pub fn read_string(cursor: &mut Cursor<&[u8]>) -> Option<String> {
     // Try VarInt first (Minecraft standard)
@@ -41,7 +92,7 @@ pub fn read_string(cursor: &mut Cursor<&[u8]>) -> Option<String> {
     let mut bytes = vec![0u8; len];
     use std::io::Read;
     cursor.read_exact(&mut bytes).ok()?;
-    String::from_utf8(bytes).ok()
+    decode_modified_utf8(&bytes).ok()
 }
 
 /// Reads a simple byte-prefixed string (single byte length + UTF-8).
@@ -52,7 +103,115 @@ pub fn read_string_byte_prefix(cursor: &mut Cursor<&[u8]>) -> Option<String> {
     let len = len_byte[0] as usize;
     let mut bytes = vec![0u8; len];
     cursor.read_exact(&mut bytes).ok()?;
-    String::from_utf8(bytes).ok()
+    decode_modified_utf8(&bytes).ok()
+}
+
+/// Decodes bytes encoded as Java "Modified UTF-8" (the format
+/// `DataOutput.writeUTF` and Minecraft's NBT strings actually use on the
+/// wire), which plain `String::from_utf8` can't handle correctly.
+///
+/// It differs from standard UTF-8 in two ways: the null code point U+0000 is
+/// written as the two-byte sequence `0xC0 0x80` instead of a single `0x00`
+/// byte, and any code point above U+FFFF is written as a CESU-8 surrogate
+/// pair - two separate three-byte sequences encoding a high surrogate
+/// (U+D800-U+DBFF) and a low surrogate (U+DC00-U+DFFF) - instead of a single
+/// four-byte UTF-8 sequence.
+pub fn decode_modified_utf8(bytes: &[u8]) -> Result<String, String> {
+    let mut result = String::with_capacity(bytes.len());
+    let mut i = 0;
+    let mut pending_high_surrogate: Option<u32> = None;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+
+        let (code_point, consumed) = if b < 0x80 {
+            (b as u32, 1)
+        } else if (b & 0xE0) == 0xC0 {
+            let next = *bytes
+                .get(i + 1)
+                .ok_or("truncated 2-byte modified UTF-8 sequence")?;
+            (((b as u32 & 0x1F) << 6) | (next as u32 & 0x3F), 2)
+        } else if (b & 0xF0) == 0xE0 {
+            let b2 = *bytes
+                .get(i + 1)
+                .ok_or("truncated 3-byte modified UTF-8 sequence")?;
+            let b3 = *bytes
+                .get(i + 2)
+                .ok_or("truncated 3-byte modified UTF-8 sequence")?;
+            (
+                ((b as u32 & 0x0F) << 12) | ((b2 as u32 & 0x3F) << 6) | (b3 as u32 & 0x3F),
+                3,
+            )
+        } else {
+            return Err(format!("invalid modified UTF-8 leading byte {b:#04x}"));
+        };
+        i += consumed;
+
+        if let Some(high) = pending_high_surrogate {
+            if (0xDC00..=0xDFFF).contains(&code_point) {
+                let combined = 0x10000 + (high - 0xD800) * 0x400 + (code_point - 0xDC00);
+                let c = char::from_u32(combined)
+                    .ok_or_else(|| format!("invalid surrogate pair producing {combined:#x}"))?;
+                result.push(c);
+                pending_high_surrogate = None;
+                continue;
+            }
+            // not actually a pair; push the lone high surrogate's
+            // replacement and fall through to handle this code point fresh
+            result.push(char::REPLACEMENT_CHARACTER);
+            pending_high_surrogate = None;
+        }
+
+        if (0xD800..=0xDBFF).contains(&code_point) {
+            pending_high_surrogate = Some(code_point);
+            continue;
+        }
+
+        match char::from_u32(code_point) {
+            Some(c) => result.push(c),
+            None => result.push(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    if pending_high_surrogate.is_some() {
+        result.push(char::REPLACEMENT_CHARACTER);
+    }
+
+    Ok(result)
+}
+
+/// Encodes `s` as Java "Modified UTF-8", the inverse of
+/// [`decode_modified_utf8`]: `U+0000` becomes the two-byte sequence `0xC0
+/// 0x80` instead of a single `0x00` byte, and any code point above `U+FFFF`
+/// is split into a CESU-8 surrogate pair of three-byte sequences instead of
+/// a single four-byte UTF-8 sequence.
+pub fn encode_modified_utf8(s: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        let code_point = c as u32;
+        if code_point == 0 {
+            out.extend_from_slice(&[0xC0, 0x80]);
+        } else if code_point < 0x80 {
+            out.push(code_point as u8);
+        } else if code_point < 0x800 {
+            out.push(0xC0 | (code_point >> 6) as u8);
+            out.push(0x80 | (code_point & 0x3F) as u8);
+        } else if code_point < 0x10000 {
+            out.push(0xE0 | (code_point >> 12) as u8);
+            out.push(0x80 | ((code_point >> 6) & 0x3F) as u8);
+            out.push(0x80 | (code_point & 0x3F) as u8);
+        } else {
+            let adjusted = code_point - 0x10000;
+            let high = 0xD800 + (adjusted >> 10);
+            let low = 0xDC00 + (adjusted & 0x3FF);
+            for surrogate in [high, low] {
+                out.push(0xE0 | (surrogate >> 12) as u8);
+                out.push(0x80 | ((surrogate >> 6) & 0x3F) as u8);
+                out.push(0x80 | (surrogate & 0x3F) as u8);
+            }
+        }
+    }
+    out
 }
 
 // ---------------------------------------------------------------------------
@@ -146,8 +305,11 @@ pub fn decode_cca_entity_sync(data: &[u8]) -> Result<CcaEntitySyncPacket, String
                 let nbt_data = data[nbt_start + 2..nbt_start + 2 + nbt_len].to_vec();
                 cursor.set_position((nbt_start + 2 + nbt_len) as u64);
 
-                // Try to parse NBT
-                let component_data = match parse_nbt(&nbt_data) {
+                // Try to parse NBT. Different CCA versions frame the
+                // per-component NBT differently (bodyless, like most config
+                // sync packets, vs. a full named/network root), so try each
+                // known framing rather than assuming one.
+                let component_data = match parse_cca_component_nbt(&nbt_data) {
                     Ok(nbt) => ComponentData::ParsedNbt(nbt),
                     Err(_) => ComponentData::Nbt(nbt_data),
                 };
@@ -183,12 +345,181 @@ pub fn decode_cca_entity_sync(data: &[u8]) -> Result<CcaEntitySyncPacket, String
 /// - Tag payload (depends on type)
 /// - End tag (0x00) for compounds
 pub fn parse_nbt(data: &[u8]) -> Result<NbtCompound, String> {
+    parse_nbt_with_root(data, NbtRootKind::Bodyless).map(|(_, compound)| compound)
+}
+
+/// The framing a buffer of NBT bytes uses, which determines whether (and
+/// how) a root tag/name precedes the compound body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NbtRootKind {
+    /// A classic file root: a `0x0A` (`TAG_Compound`) byte, then a (usually
+    /// empty) root name, then the body.
+    Named,
+    /// The modern network form (1.20.2+): a `0x0A` byte straight into the
+    /// body, with the root name dropped entirely.
+    Network,
+    /// No root wrapper at all, straight into the body. What [`parse_nbt`]
+    /// has always assumed, and what most CCA component payloads use.
+    Bodyless,
+}
+
+/// Parses NBT bytes under an explicit [`NbtRootKind`], returning the root
+/// name when the framing has one.
+///
+/// Reading a named or network root as bodyless (or vice versa) desyncs the
+/// parser: it misreads the root name's length bytes as the first member's
+/// tag type, or an absent root tag byte as a member's tag type.
+pub fn parse_nbt_with_root(
+    data: &[u8],
+    root_kind: NbtRootKind,
+) -> Result<(Option<String>, NbtCompound), String> {
+    parse_nbt_with_root_checked(data, root_kind).map(|(name, compound, _)| (name, compound))
+}
+
+/// Like [`parse_nbt_with_root`], but also reports whether `data` was fully
+/// consumed by this framing, so a caller trying multiple framings against
+/// the same buffer (see [`parse_cca_component_nbt`]) can tell "this framing
+/// parsed" apart from "this framing parsed a prefix of the buffer and left
+/// the rest misread as nothing".
+fn parse_nbt_with_root_checked(
+    data: &[u8],
+    root_kind: NbtRootKind,
+) -> Result<(Option<String>, NbtCompound, bool), String> {
+    let data = decompress_nbt(data);
     if data.is_empty() {
-        return Ok(NbtCompound::default());
+        return Ok((None, NbtCompound::default(), true));
     }
 
-    let mut cursor = Cursor::new(data);
-    read_nbt_compound(&mut cursor)
+    let mut cursor = Cursor::new(data.as_ref());
+
+    let (name, compound) = match root_kind {
+        NbtRootKind::Bodyless => (None, read_nbt_compound(&mut cursor)?),
+        NbtRootKind::Network => {
+            expect_root_compound_tag(&mut cursor)?;
+            (None, read_nbt_compound(&mut cursor)?)
+        }
+        NbtRootKind::Named => {
+            expect_root_compound_tag(&mut cursor)?;
+            let name = read_nbt_string(&mut cursor)?;
+            (Some(name), read_nbt_compound(&mut cursor)?)
+        }
+    };
+
+    let fully_consumed = cursor.position() as usize == data.len();
+    Ok((name, compound, fully_consumed))
+}
+
+/// Parses a classic named-root NBT buffer, returning the root name.
+pub fn parse_nbt_named(data: &[u8]) -> Result<(String, NbtCompound), String> {
+    let (name, compound) = parse_nbt_with_root(data, NbtRootKind::Named)?;
+    Ok((name.unwrap_or_default(), compound))
+}
+
+/// Parses a network-form (1.20.2+) NBT buffer, which has a root tag byte
+/// but no root name.
+pub fn parse_nbt_network(data: &[u8]) -> Result<NbtCompound, String> {
+    parse_nbt_with_root(data, NbtRootKind::Network).map(|(_, compound)| compound)
+}
+
+/// Parses a bodyless NBT buffer (no root wrapper at all). Equivalent to
+/// [`parse_nbt`].
+pub fn parse_nbt_body(data: &[u8]) -> Result<NbtCompound, String> {
+    parse_nbt(data)
+}
+
+fn expect_root_compound_tag(cursor: &mut Cursor<&[u8]>) -> Result<(), String> {
+    let tag_type = read_u8(cursor).ok_or_else(|| "missing root tag byte".to_string())?;
+    if tag_type != 10 {
+        return Err(format!(
+            "expected root TAG_Compound (10), got tag type {tag_type}"
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a CCA component's NBT blob, trying each known root framing in
+/// turn. CCA versions aren't self-describing about which one they used, so
+/// this can't dispatch on a version field; instead it requires the
+/// candidate framing to consume the *entire* buffer before accepting it; a
+/// wrong framing can still decode a `TAG_End`-terminated prefix of the
+/// bytes without error (`read_nbt_compound` just stops at the first
+/// `0x00`), so "parsed without error" alone isn't enough to tell a correct
+/// framing from a lucky misread of a different one.
+fn parse_cca_component_nbt(data: &[u8]) -> Result<NbtCompound, String> {
+    for root_kind in [
+        NbtRootKind::Bodyless,
+        NbtRootKind::Network,
+        NbtRootKind::Named,
+    ] {
+        if let Ok((_, compound, fully_consumed)) = parse_nbt_with_root_checked(data, root_kind) {
+            if fully_consumed {
+                return Ok(compound);
+            }
+        }
+    }
+    Err("failed to parse CCA component NBT under any known root framing".to_string())
+}
+
+/// Transparently decompresses `data` if it's gzip or zlib-wrapped NBT,
+/// passing it through unchanged otherwise.
+///
+/// Modded packets and saved NBT often arrive compressed; this peeks the
+/// leading magic bytes so callers (chiefly [`parse_nbt`]) don't have to
+/// guess the framing. `0x1F 0x8B` is the gzip magic; a zlib header is
+/// `0x78` followed by a byte that makes the big-endian first two bytes a
+/// multiple of 31 (e.g. `0x78 0x9C`, `0x78 0x01`, `0x78 0xDA`).
+pub fn decompress_nbt(data: &[u8]) -> Cow<'_, [u8]> {
+    if data.len() >= 2 && data[0] == 0x1F && data[1] == 0x8B {
+        match inflate_gzip(data) {
+            Ok(decompressed) => return Cow::Owned(decompressed),
+            Err(err) => tracing::warn!("Failed to gunzip NBT data, parsing as-is: {err}"),
+        }
+    } else if is_zlib_header(data) {
+        match inflate_zlib(data) {
+            Ok(decompressed) => return Cow::Owned(decompressed),
+            Err(err) => tracing::warn!("Failed to inflate zlib NBT data, parsing as-is: {err}"),
+        }
+    }
+
+    Cow::Borrowed(data)
+}
+
+fn is_zlib_header(data: &[u8]) -> bool {
+    data.len() >= 2 && data[0] == 0x78 && u16::from_be_bytes([data[0], data[1]]) % 31 == 0
+}
+
+/// Upper bound on how much a single compressed NBT blob is allowed to
+/// inflate to. Without this, a malicious/compromised server can send a
+/// small, highly-compressible blob (e.g. as a CCA component payload, which
+/// the server fully controls) and exhaust memory decompressing it.
+const MAX_DECOMPRESSED_NBT_BYTES: u64 = 8 * 1024 * 1024;
+
+fn inflate_gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .take(MAX_DECOMPRESSED_NBT_BYTES + 1)
+        .read_to_end(&mut out)?;
+    if out.len() as u64 > MAX_DECOMPRESSED_NBT_BYTES {
+        return Err(std::io::Error::other(format!(
+            "decompressed NBT exceeds {MAX_DECOMPRESSED_NBT_BYTES} byte limit"
+        )));
+    }
+    Ok(out)
+}
+
+fn inflate_zlib(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut out = Vec::new();
+    flate2::read::ZlibDecoder::new(data)
+        .take(MAX_DECOMPRESSED_NBT_BYTES + 1)
+        .read_to_end(&mut out)?;
+    if out.len() as u64 > MAX_DECOMPRESSED_NBT_BYTES {
+        return Err(std::io::Error::other(format!(
+            "decompressed NBT exceeds {MAX_DECOMPRESSED_NBT_BYTES} byte limit"
+        )));
+    }
+    Ok(out)
 }
 
 fn read_nbt_compound(cursor: &mut Cursor<&[u8]>) -> Result<NbtCompound, String> {
@@ -233,7 +564,7 @@ fn read_nbt_string(cursor: &mut Cursor<&[u8]>) -> Result<String, String> {
     let mut bytes = vec![0u8; len];
     cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
 
-    String::from_utf8(bytes).map_err(|e| e.to_string())
+    decode_modified_utf8(&bytes)
 }
 
 fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag, String> {
@@ -278,11 +609,7 @@ fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag,
         }
         7 => {
             // TAG_Byte_Array
-            let mut len_bytes = [0u8; 4];
-            cursor
-                .read_exact(&mut len_bytes)
-                .map_err(|e| e.to_string())?;
-            let len = i32::from_be_bytes(len_bytes) as usize;
+            let len = read_array_len(cursor)?;
             let mut bytes = vec![0u8; len];
             cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
             Ok(NbtTag::ByteArray(
@@ -301,11 +628,7 @@ fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag,
                 .map_err(|e| e.to_string())?;
             let list_type = type_byte[0];
 
-            let mut len_bytes = [0u8; 4];
-            cursor
-                .read_exact(&mut len_bytes)
-                .map_err(|e| e.to_string())?;
-            let len = i32::from_be_bytes(len_bytes) as usize;
+            let len = read_array_len(cursor)?;
 
             let mut items = Vec::with_capacity(len);
             for _ in 0..len {
@@ -319,11 +642,7 @@ fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag,
         }
         11 => {
             // TAG_Int_Array
-            let mut len_bytes = [0u8; 4];
-            cursor
-                .read_exact(&mut len_bytes)
-                .map_err(|e| e.to_string())?;
-            let len = i32::from_be_bytes(len_bytes) as usize;
+            let len = read_array_len(cursor)?;
 
             let mut items = Vec::with_capacity(len);
             for _ in 0..len {
@@ -335,11 +654,7 @@ fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag,
         }
         12 => {
             // TAG_Long_Array
-            let mut len_bytes = [0u8; 4];
-            cursor
-                .read_exact(&mut len_bytes)
-                .map_err(|e| e.to_string())?;
-            let len = i32::from_be_bytes(len_bytes) as usize;
+            let len = read_array_len(cursor)?;
 
             let mut items = Vec::with_capacity(len);
             for _ in 0..len {
@@ -353,6 +668,378 @@ fn read_nbt_payload(cursor: &mut Cursor<&[u8]>, tag_type: u8) -> Result<NbtTag,
     }
 }
 
+// ---------------------------------------------------------------------------
+// NBT encoding
+// ---------------------------------------------------------------------------
+
+/// Encodes an [`NbtCompound`] back to bytes, the inverse of
+/// [`read_nbt_compound`] (and thus of [`parse_nbt`]).
+pub fn encode_nbt(compound: &NbtCompound) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_nbt_compound_body(compound, &mut out);
+    out
+}
+
+fn write_nbt_compound_body(compound: &NbtCompound, out: &mut Vec<u8>) {
+    for (name, tag) in &compound.tags {
+        out.push(nbt_tag_type(tag));
+        write_nbt_string(name, out);
+        tag.write(out);
+    }
+    out.push(0); // TAG_End
+}
+
+fn write_nbt_string(s: &str, out: &mut Vec<u8>) {
+    let bytes = encode_modified_utf8(s);
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+fn nbt_tag_type(tag: &NbtTag) -> u8 {
+    match tag {
+        NbtTag::Byte(_) => 1,
+        NbtTag::Short(_) => 2,
+        NbtTag::Int(_) => 3,
+        NbtTag::Long(_) => 4,
+        NbtTag::Float(_) => 5,
+        NbtTag::Double(_) => 6,
+        NbtTag::ByteArray(_) => 7,
+        NbtTag::String(_) => 8,
+        NbtTag::List(_) => 9,
+        NbtTag::Compound(_) => 10,
+        NbtTag::IntArray(_) => 11,
+        NbtTag::LongArray(_) => 12,
+    }
+}
+
+impl NbtTag {
+    /// Writes this tag's payload to `out`, mirroring [`read_nbt_payload`].
+    /// Unlike a full compound member, this doesn't write a type byte or
+    /// name, since list elements carry neither.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            NbtTag::Byte(v) => out.push(*v as u8),
+            NbtTag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+            NbtTag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+            NbtTag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+            NbtTag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+            NbtTag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+            NbtTag::ByteArray(items) => {
+                out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+                out.extend(items.iter().map(|b| *b as u8));
+            }
+            NbtTag::String(s) => write_nbt_string(s, out),
+            NbtTag::List(items) => {
+                let element_type = items.first().map(nbt_tag_type).unwrap_or(0);
+                out.push(element_type);
+                out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+                for item in items {
+                    item.write(out);
+                }
+            }
+            NbtTag::Compound(compound) => write_nbt_compound_body(compound, out),
+            NbtTag::IntArray(items) => {
+                out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+                for v in items {
+                    out.extend_from_slice(&v.to_be_bytes());
+                }
+            }
+            NbtTag::LongArray(items) => {
+                out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+                for v in items {
+                    out.extend_from_slice(&v.to_be_bytes());
+                }
+            }
+        }
+    }
+}
+
+impl CcaEntitySyncPacket {
+    /// Re-encodes this packet back to wire format: a VarInt entity id, then
+    /// each component's VarInt-prefixed type string and `u16`
+    /// length-prefixed NBT blob, mirroring [`decode_cca_entity_sync`].
+    ///
+    /// Components whose NBT wasn't parsed are re-emitted as their original
+    /// raw bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_varint(&mut out, self.entity_id);
+
+        for component in &self.components {
+            write_varint_prefixed_string(&mut out, &component.component_type);
+
+            let nbt_bytes = match &component.data {
+                ComponentData::ParsedNbt(nbt) => encode_nbt(nbt),
+                ComponentData::Nbt(bytes) | ComponentData::Unknown(bytes) => bytes.clone(),
+            };
+            out.extend_from_slice(&(nbt_bytes.len() as u16).to_be_bytes());
+            out.extend_from_slice(&nbt_bytes);
+        }
+
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming parser
+// ---------------------------------------------------------------------------
+
+/// A shallow, single NBT value or structural marker produced by
+/// [`NbtStream`].
+///
+/// Names are `Some` for compound members and `None` for list elements and
+/// end markers, matching the binary format (list elements and end markers
+/// carry no name on the wire).
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtEvent {
+    Byte(Option<String>, i8),
+    Short(Option<String>, i16),
+    Int(Option<String>, i32),
+    Long(Option<String>, i64),
+    Float(Option<String>, f32),
+    Double(Option<String>, f64),
+    ByteArray(Option<String>, Vec<i8>),
+    String(Option<String>, String),
+    /// A list's element tag type and length; its elements follow as events,
+    /// terminated by [`NbtEvent::ListEnd`].
+    List(Option<String>, u8, i32),
+    ListEnd,
+    /// A compound's members follow as events, terminated by
+    /// [`NbtEvent::CompoundEnd`].
+    Compound(Option<String>),
+    CompoundEnd,
+    IntArray(Option<String>, Vec<i32>),
+    LongArray(Option<String>, Vec<i64>),
+}
+
+enum NbtStreamFrame {
+    Compound,
+    List { element_type: u8, remaining: i32 },
+}
+
+/// A pull parser over NBT bytes that emits shallow [`NbtEvent`]s instead of
+/// building a full [`NbtCompound`] tree, so callers scanning a large blob
+/// for a single field (e.g. `"color"` or `"owner"`) don't have to allocate
+/// the whole thing.
+///
+/// Mirrors [`parse_nbt`]'s framing: the buffer is the bodyless form (no
+/// leading tag-type/name for the root), so the stream starts as if already
+/// inside the root compound.
+pub struct NbtStream<'a> {
+    cursor: Cursor<&'a [u8]>,
+    stack: Vec<NbtStreamFrame>,
+    finished: bool,
+}
+
+impl<'a> NbtStream<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            cursor: Cursor::new(data),
+            stack: vec![NbtStreamFrame::Compound],
+            finished: false,
+        }
+    }
+
+    /// Returns the next event, or `None` once the root compound has been
+    /// fully consumed (or an error ended the stream early).
+    pub fn next(&mut self) -> Option<Result<NbtEvent, String>> {
+        if self.finished || self.stack.is_empty() {
+            return None;
+        }
+
+        match self.stack.last_mut().unwrap() {
+            NbtStreamFrame::List {
+                element_type,
+                remaining,
+            } => {
+                if *remaining == 0 {
+                    self.stack.pop();
+                    return Some(Ok(NbtEvent::ListEnd));
+                }
+                *remaining -= 1;
+                let element_type = *element_type;
+                let event = self.read_event(element_type, None);
+                if event.is_err() {
+                    self.finished = true;
+                }
+                Some(event)
+            }
+            NbtStreamFrame::Compound => {
+                let tag_type = match read_u8(&mut self.cursor) {
+                    Some(b) => b,
+                    None => {
+                        self.finished = true;
+                        return Some(Err("unexpected end of data in compound".to_string()));
+                    }
+                };
+
+                if tag_type == 0 {
+                    self.stack.pop();
+                    // the implicit root compound's end isn't paired with a
+                    // start event, so it doesn't get an end event either
+                    return if self.stack.is_empty() {
+                        self.finished = true;
+                        None
+                    } else {
+                        Some(Ok(NbtEvent::CompoundEnd))
+                    };
+                }
+
+                let name = match read_nbt_string(&mut self.cursor) {
+                    Ok(name) => name,
+                    Err(err) => {
+                        self.finished = true;
+                        return Some(Err(err));
+                    }
+                };
+                let event = self.read_event(tag_type, Some(name));
+                if event.is_err() {
+                    self.finished = true;
+                }
+                Some(event)
+            }
+        }
+    }
+
+    /// Skips the subtree just opened by the last [`NbtEvent::Compound`] or
+    /// [`NbtEvent::List`] event this stream produced, without allocating it.
+    pub fn skip_subtree(&mut self) -> Result<(), String> {
+        let depth = self.stack.len();
+        while self.stack.len() >= depth {
+            match self.next() {
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_event(&mut self, tag_type: u8, name: Option<String>) -> Result<NbtEvent, String> {
+        match tag_type {
+            1 => read_i8(&mut self.cursor).map(|v| NbtEvent::Byte(name, v)),
+            2 => read_i16(&mut self.cursor).map(|v| NbtEvent::Short(name, v)),
+            3 => read_i32(&mut self.cursor).map(|v| NbtEvent::Int(name, v)),
+            4 => read_i64(&mut self.cursor).map(|v| NbtEvent::Long(name, v)),
+            5 => read_f32(&mut self.cursor).map(|v| NbtEvent::Float(name, v)),
+            6 => read_f64(&mut self.cursor).map(|v| NbtEvent::Double(name, v)),
+            7 => read_i8_array(&mut self.cursor).map(|v| NbtEvent::ByteArray(name, v)),
+            8 => read_nbt_string(&mut self.cursor).map(|v| NbtEvent::String(name, v)),
+            9 => {
+                let element_type =
+                    read_u8(&mut self.cursor).ok_or_else(|| "truncated list header".to_string())?;
+                let len = read_i32(&mut self.cursor)?;
+                if len < 0 {
+                    return Err(format!("negative NBT list length: {len}"));
+                }
+                self.stack.push(NbtStreamFrame::List {
+                    element_type,
+                    remaining: len,
+                });
+                Ok(NbtEvent::List(name, element_type, len))
+            }
+            10 => {
+                self.stack.push(NbtStreamFrame::Compound);
+                Ok(NbtEvent::Compound(name))
+            }
+            11 => read_i32_array(&mut self.cursor).map(|v| NbtEvent::IntArray(name, v)),
+            12 => read_i64_array(&mut self.cursor).map(|v| NbtEvent::LongArray(name, v)),
+            other => Err(format!("Unknown NBT tag type: {other}")),
+        }
+    }
+}
+
+fn read_u8(cursor: &mut Cursor<&[u8]>) -> Option<u8> {
+    use std::io::Read;
+    let mut byte = [0u8; 1];
+    cursor.read_exact(&mut byte).ok()?;
+    Some(byte[0])
+}
+
+fn read_i8(cursor: &mut Cursor<&[u8]>) -> Result<i8, String> {
+    read_u8(cursor)
+        .map(|b| b as i8)
+        .ok_or_else(|| "unexpected end of data reading i8".to_string())
+}
+
+fn read_i16(cursor: &mut Cursor<&[u8]>) -> Result<i16, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 2];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(i16::from_be_bytes(bytes))
+}
+
+fn read_i32(cursor: &mut Cursor<&[u8]>) -> Result<i32, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 4];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+fn read_i64(cursor: &mut Cursor<&[u8]>) -> Result<i64, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 8];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(i64::from_be_bytes(bytes))
+}
+
+fn read_f32(cursor: &mut Cursor<&[u8]>) -> Result<f32, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 4];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(f32::from_be_bytes(bytes))
+}
+
+fn read_f64(cursor: &mut Cursor<&[u8]>) -> Result<f64, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 8];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(f64::from_be_bytes(bytes))
+}
+
+/// Reads and validates an NBT array-tag length prefix.
+///
+/// The prefix is a raw `i32`; a negative value (trivial for a malicious
+/// server to send in e.g. a CCA component's `IntArray`/`LongArray`) would
+/// otherwise sign-extend into a huge `usize` and panic
+/// `Vec::with_capacity`/`vec![]` with "capacity overflow" instead of
+/// returning `Err` like every other parse failure here does. (`forge_handshake.rs`'s
+/// `read_mod_list` guards the same sign-extension differently: it clamps a
+/// negative count to zero with `count.max(0)` instead of failing.)
+fn read_array_len(cursor: &mut Cursor<&[u8]>) -> Result<usize, String> {
+    let len = read_i32(cursor)?;
+    if len < 0 {
+        return Err(format!("negative NBT array length: {len}"));
+    }
+    Ok(len as usize)
+}
+
+fn read_i8_array(cursor: &mut Cursor<&[u8]>) -> Result<Vec<i8>, String> {
+    use std::io::Read;
+    let len = read_array_len(cursor)?;
+    let mut bytes = vec![0u8; len];
+    cursor.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes.into_iter().map(|b| b as i8).collect())
+}
+
+fn read_i32_array(cursor: &mut Cursor<&[u8]>) -> Result<Vec<i32>, String> {
+    let len = read_array_len(cursor)?;
+    let mut items = Vec::with_capacity(len);
+    for _ in 0..len {
+        items.push(read_i32(cursor)?);
+    }
+    Ok(items)
+}
+
+fn read_i64_array(cursor: &mut Cursor<&[u8]>) -> Result<Vec<i64>, String> {
+    let len = read_array_len(cursor)?;
+    let mut items = Vec::with_capacity(len);
+    for _ in 0..len {
+        items.push(read_i64(cursor)?);
+    }
+    Ok(items)
+}
+
 // ---------------------------------------------------------------------------
 // Pretty printing
 // ---------------------------------------------------------------------------
@@ -398,6 +1085,107 @@ pub fn format_nbt_tag(tag: &NbtTag) -> String {
     }
 }
 
+// ---------------------------------------------------------------------------
+// SNBT (Mojangson) serialization
+// ---------------------------------------------------------------------------
+
+/// Serializes an [`NbtCompound`] to canonical SNBT (Mojangson) text, e.g.
+/// `{color:"red",count:3b}`, unlike [`format_nbt_tag`]'s lossy
+/// `"[3 items]"`-style summaries. The result can be pasted into a `/data`
+/// command or diffed across ticks.
+pub fn to_snbt(compound: &NbtCompound) -> String {
+    let mut out = String::new();
+    write_snbt_compound(compound, &mut out);
+    out
+}
+
+/// Serializes a single [`NbtTag`] to SNBT text.
+pub fn tag_to_snbt(tag: &NbtTag) -> String {
+    let mut out = String::new();
+    write_snbt_tag(tag, &mut out);
+    out
+}
+
+fn write_snbt_compound(compound: &NbtCompound, out: &mut String) {
+    out.push('{');
+    for (i, (name, tag)) in compound.tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_snbt_key(name, out);
+        out.push(':');
+        write_snbt_tag(tag, out);
+    }
+    out.push('}');
+}
+
+fn write_snbt_tag(tag: &NbtTag, out: &mut String) {
+    match tag {
+        NbtTag::Byte(v) => out.push_str(&format!("{v}b")),
+        NbtTag::Short(v) => out.push_str(&format!("{v}s")),
+        NbtTag::Int(v) => out.push_str(&format!("{v}")),
+        NbtTag::Long(v) => out.push_str(&format!("{v}L")),
+        NbtTag::Float(v) => out.push_str(&format!("{v}f")),
+        NbtTag::Double(v) => out.push_str(&format!("{v}d")),
+        NbtTag::ByteArray(items) => write_snbt_array(out, "B", items, |v| format!("{v}b")),
+        NbtTag::String(s) => write_snbt_string(s, out),
+        NbtTag::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_snbt_tag(item, out);
+            }
+            out.push(']');
+        }
+        NbtTag::Compound(compound) => write_snbt_compound(compound, out),
+        NbtTag::IntArray(items) => write_snbt_array(out, "I", items, |v| format!("{v}")),
+        NbtTag::LongArray(items) => write_snbt_array(out, "L", items, |v| format!("{v}L")),
+    }
+}
+
+fn write_snbt_array<T>(out: &mut String, prefix: &str, items: &[T], format_item: impl Fn(&T) -> String) {
+    out.push('[');
+    out.push_str(prefix);
+    out.push(';');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format_item(item));
+    }
+    out.push(']');
+}
+
+fn write_snbt_key(name: &str, out: &mut String) {
+    if is_snbt_identifier(name) {
+        out.push_str(name);
+    } else {
+        write_snbt_string(name, out);
+    }
+}
+
+/// Whether `s` can be written as a bare SNBT key/string, i.e. it matches
+/// Mojang's `[A-Za-z0-9._+-]+` identifier pattern.
+fn is_snbt_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+fn write_snbt_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -420,4 +1208,287 @@ mod tests {
         let mut cursor = Cursor::new(data.as_slice());
         assert_eq!(read_varint(&mut cursor), Some(128));
     }
+
+    #[test]
+    fn test_varshort() {
+        // fits in the low 15 bits, no continuation byte
+        let data = vec![0x00, 0x05];
+        let mut cursor = Cursor::new(data.as_slice());
+        assert_eq!(read_varshort(&mut cursor), Some(5));
+
+        // high bit of the low short set, so a continuation byte follows
+        let data = vec![0x80, 0x00, 0x01];
+        let mut cursor = Cursor::new(data.as_slice());
+        assert_eq!(read_varshort(&mut cursor), Some(1 << 15));
+    }
+
+    #[test]
+    fn test_decode_modified_utf8() {
+        // plain ASCII round-trips
+        assert_eq!(decode_modified_utf8(b"hello"), Ok("hello".to_string()));
+
+        // the null code point is encoded as 0xC0 0x80, not a single 0x00
+        assert_eq!(decode_modified_utf8(&[0xC0, 0x80]), Ok("\0".to_string()));
+
+        // a code point above U+FFFF (here U+1F600, 😀) is written as a
+        // CESU-8 surrogate pair of two three-byte sequences
+        let surrogate_pair = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!(decode_modified_utf8(&surrogate_pair), Ok("\u{1F600}".to_string()));
+    }
+
+    #[test]
+    fn test_encode_nbt_round_trip() {
+        let compound = NbtCompound {
+            tags: vec![
+                ("name".to_string(), NbtTag::String("backpack".to_string())),
+                ("slots".to_string(), NbtTag::Int(27)),
+                (
+                    "items".to_string(),
+                    NbtTag::List(vec![NbtTag::Byte(1), NbtTag::Byte(2)]),
+                ),
+            ],
+        };
+
+        let encoded = encode_nbt(&compound);
+        let decoded = parse_nbt(&encoded).unwrap();
+
+        assert_eq!(decoded.tags.len(), compound.tags.len());
+        assert_eq!(format_nbt_tag(&decoded.tags[1].1), "27");
+    }
+
+    #[test]
+    fn test_encode_nbt_string_modified_utf8_round_trip() {
+        // a null code point and a code point above U+FFFF (😀) must survive
+        // an encode_nbt -> parse_nbt round trip, not just plain ASCII
+        let compound = NbtCompound {
+            tags: vec![("name".to_string(), NbtTag::String("back\u{0}pack\u{1F600}".to_string()))],
+        };
+
+        let encoded = encode_nbt(&compound);
+        let decoded = parse_nbt(&encoded).unwrap();
+
+        assert_eq!(format_nbt_tag(&decoded.tags[0].1), "\"back\u{0}pack\u{1F600}\"");
+    }
+
+    #[test]
+    fn test_write_varint_prefixed_string_modified_utf8_round_trip() {
+        // a null code point and a code point above U+FFFF (😀) must survive
+        // a write_varint_prefixed_string -> read_string round trip, since
+        // read_string decodes via decode_modified_utf8
+        let s = "back\u{0}pack\u{1F600}";
+        let mut out = Vec::new();
+        write_varint_prefixed_string(&mut out, s);
+
+        let mut cursor = Cursor::new(out.as_slice());
+        assert_eq!(read_string(&mut cursor), Some(s.to_string()));
+    }
+
+    #[test]
+    fn test_nbt_stream() {
+        let compound = NbtCompound {
+            tags: vec![
+                ("color".to_string(), NbtTag::String("red".to_string())),
+                (
+                    "nested".to_string(),
+                    NbtTag::Compound(NbtCompound {
+                        tags: vec![("owner".to_string(), NbtTag::String("steve".to_string()))],
+                    }),
+                ),
+            ],
+        };
+        let encoded = encode_nbt(&compound);
+
+        let mut stream = NbtStream::new(&encoded);
+        assert_eq!(
+            stream.next(),
+            Some(Ok(NbtEvent::String(
+                Some("color".to_string()),
+                "red".to_string()
+            )))
+        );
+        assert_eq!(
+            stream.next(),
+            Some(Ok(NbtEvent::Compound(Some("nested".to_string()))))
+        );
+        assert_eq!(
+            stream.next(),
+            Some(Ok(NbtEvent::String(
+                Some("owner".to_string()),
+                "steve".to_string()
+            )))
+        );
+        assert_eq!(stream.next(), Some(Ok(NbtEvent::CompoundEnd)));
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn test_nbt_stream_rejects_negative_array_length() {
+        // TAG_Int_Array (11) named "n", with a negative length prefix
+        let mut data = vec![11, 0, 1, b'n'];
+        data.extend_from_slice(&(-1i32).to_be_bytes());
+
+        let mut stream = NbtStream::new(&data);
+        assert_eq!(
+            stream.next(),
+            Some(Err("negative NBT array length: -1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_nbt_stream_rejects_negative_list_length() {
+        // TAG_List (9) named "n", element type TAG_Int (3), negative length
+        let mut data = vec![9, 0, 1, b'n', 3];
+        data.extend_from_slice(&(-1i32).to_be_bytes());
+
+        let mut stream = NbtStream::new(&data);
+        assert_eq!(
+            stream.next(),
+            Some(Err("negative NBT list length: -1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_nbt_payload_rejects_negative_array_length() {
+        // TAG_Int_Array (11) with a negative length prefix, as the eager
+        // parser used by parse_nbt/read_nbt_compound would see it; this must
+        // return an error instead of panicking with "capacity overflow"
+        let mut data = vec![11, 0, 1, b'n'];
+        data.extend_from_slice(&(-1i32).to_be_bytes());
+
+        assert!(parse_nbt(&data).is_err());
+    }
+
+    #[test]
+    fn test_nbt_stream_skip_subtree() {
+        let compound = NbtCompound {
+            tags: vec![
+                (
+                    "nested".to_string(),
+                    NbtTag::Compound(NbtCompound {
+                        tags: vec![("junk".to_string(), NbtTag::Int(1))],
+                    }),
+                ),
+                ("after".to_string(), NbtTag::Int(42)),
+            ],
+        };
+        let encoded = encode_nbt(&compound);
+
+        let mut stream = NbtStream::new(&encoded);
+        assert_eq!(
+            stream.next(),
+            Some(Ok(NbtEvent::Compound(Some("nested".to_string()))))
+        );
+        stream.skip_subtree().unwrap();
+        assert_eq!(
+            stream.next(),
+            Some(Ok(NbtEvent::Int(Some("after".to_string()), 42)))
+        );
+    }
+
+    #[test]
+    fn test_decompress_nbt_passthrough() {
+        let compound = NbtCompound {
+            tags: vec![("a".to_string(), NbtTag::Int(1))],
+        };
+        let encoded = encode_nbt(&compound);
+        assert_eq!(decompress_nbt(&encoded).as_ref(), encoded.as_slice());
+    }
+
+    #[test]
+    fn test_is_zlib_header() {
+        assert!(is_zlib_header(&[0x78, 0x9C]));
+        assert!(is_zlib_header(&[0x78, 0x01]));
+        assert!(is_zlib_header(&[0x78, 0xDA]));
+        assert!(!is_zlib_header(&[0x78, 0xFF]));
+        assert!(!is_zlib_header(&[0x1F, 0x8B]));
+    }
+
+    #[test]
+    fn test_decompress_nbt_gzip() {
+        use std::io::Write;
+
+        let compound = NbtCompound {
+            tags: vec![("a".to_string(), NbtTag::Int(1))],
+        };
+        let encoded = encode_nbt(&compound);
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&encoded).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        assert_eq!(decompress_nbt(&gzipped).as_ref(), encoded.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_nbt_gzip_bomb_falls_back_instead_of_exhausting_memory() {
+        use std::io::Write;
+
+        // a small, highly-compressible blob that would inflate to far more
+        // than MAX_DECOMPRESSED_NBT_BYTES if fully decompressed
+        let huge = vec![0u8; (MAX_DECOMPRESSED_NBT_BYTES * 2) as usize];
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&huge).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        // decompress_nbt falls back to the (small) compressed bytes as-is
+        // on a decompression error, rather than returning gigabytes of data
+        assert_eq!(decompress_nbt(&gzipped).as_ref(), gzipped.as_slice());
+    }
+
+    #[test]
+    fn test_to_snbt() {
+        let compound = NbtCompound {
+            tags: vec![
+                ("color".to_string(), NbtTag::String("red".to_string())),
+                ("count".to_string(), NbtTag::Byte(3)),
+                (
+                    "weird key".to_string(),
+                    NbtTag::List(vec![NbtTag::Int(1), NbtTag::Int(2)]),
+                ),
+                ("ids".to_string(), NbtTag::IntArray(vec![1, 2, 3])),
+            ],
+        };
+
+        assert_eq!(
+            to_snbt(&compound),
+            "{color:\"red\",count:3b,\"weird key\":[1,2],ids:[I;1,2,3]}"
+        );
+    }
+
+    #[test]
+    fn test_snbt_string_escaping() {
+        let tag = NbtTag::String("say \"hi\"\\bye".to_string());
+        assert_eq!(tag_to_snbt(&tag), "\"say \\\"hi\\\"\\\\bye\"");
+    }
+
+    #[test]
+    fn test_parse_nbt_named_root() {
+        let compound = NbtCompound {
+            tags: vec![("a".to_string(), NbtTag::Int(1))],
+        };
+        let mut data = vec![0x0A]; // TAG_Compound
+        data.extend_from_slice(&0u16.to_be_bytes()); // empty root name
+        data.extend_from_slice(&encode_nbt(&compound));
+
+        let (name, decoded) = parse_nbt_named(&data).unwrap();
+        assert_eq!(name, "");
+        assert_eq!(decoded.tags.len(), 1);
+
+        // parsing the same bytes as bodyless desyncs on the root name,
+        // rather than recovering the single `a` member
+        let bodyless = parse_nbt(&data);
+        assert!(!matches!(bodyless, Ok(ref c) if c.tags.len() == 1 && c.tags[0].0 == "a"));
+    }
+
+    #[test]
+    fn test_parse_nbt_network_root() {
+        let compound = NbtCompound {
+            tags: vec![("a".to_string(), NbtTag::Int(1))],
+        };
+        let mut data = vec![0x0A]; // TAG_Compound, no root name
+        data.extend_from_slice(&encode_nbt(&compound));
+
+        let decoded = parse_nbt_network(&data).unwrap();
+        assert_eq!(decoded.tags.len(), 1);
+    }
 }
@@ -0,0 +1,151 @@
+//! Decodes Cardinal Components API `entity_sync` payloads into ECS
+//! components and events, instead of discarding them.
+//!
+//! Mods built on CCA (e.g. Traveler's Backpack) sync meaningful per-entity
+//! state over `cardinal-components:entity_sync`. Mirroring how the game
+//! packet handler decodes entity metadata and applies it to entities: parse
+//! the wire format, resolve the target entity through the entity-id index,
+//! then emit a [`ComponentSyncEvent`] and update [`ModdedComponents`] so bot
+//! authors get programmatic access instead of a dropped packet.
+
+use std::collections::HashMap;
+
+use azalea_entity::indexing::EntityIdIndex;
+use bevy_ecs::prelude::*;
+use tracing::{debug, warn};
+
+use crate::packet_decoder::{self, ComponentData, NbtCompound};
+
+/// Fired once per component carried in a decoded
+/// `cardinal-components:entity_sync` packet, for an entity we know about.
+/// Components whose NBT couldn't be parsed don't fire this event.
+#[derive(Debug, Clone, Event)]
+pub struct ComponentSyncEvent {
+    pub entity: Entity,
+    pub component_id: String,
+    pub data: NbtCompound,
+}
+
+/// The most recently synced CCA components for an entity, keyed by
+/// component id (e.g. `travelersbackpack:backpack`).
+#[derive(Component, Debug, Default, Clone)]
+pub struct ModdedComponents {
+    pub components: HashMap<String, NbtCompound>,
+}
+
+/// Decodes a raw `cardinal-components:entity_sync` payload and applies it:
+/// resolves the target entity, updates its [`ModdedComponents`], and emits a
+/// [`ComponentSyncEvent`] per component. Components whose NBT couldn't be
+/// parsed are skipped: they update nothing and emit no event.
+pub fn handle_cca_entity_sync(ecs: &mut World, _player: Entity, data: &[u8]) {
+    let packet = match packet_decoder::decode_cca_entity_sync(data) {
+        Ok(packet) => packet,
+        Err(err) => {
+            warn!("Failed to decode cardinal-components:entity_sync: {err}");
+            return;
+        }
+    };
+
+    let Some(target) = ecs
+        .get_resource::<EntityIdIndex>()
+        .and_then(|index| index.get(packet.entity_id as u32))
+    else {
+        debug!(
+            "Got entity_sync for unknown entity id {}, ignoring",
+            packet.entity_id
+        );
+        return;
+    };
+
+    for component in packet.components {
+        let nbt = match component.data {
+            ComponentData::ParsedNbt(nbt) => nbt,
+            ComponentData::Nbt(_) | ComponentData::Unknown(_) => {
+                debug!(
+                    "Skipping unparsed CCA component {} for entity {target:?}",
+                    component.component_type
+                );
+                continue;
+            }
+        };
+
+        {
+            let mut entity_mut = ecs.entity_mut(target);
+            if let Some(mut modded_components) = entity_mut.get_mut::<ModdedComponents>() {
+                modded_components
+                    .components
+                    .insert(component.component_type.clone(), nbt.clone());
+            } else {
+                entity_mut.insert(ModdedComponents {
+                    components: HashMap::from([(component.component_type.clone(), nbt.clone())]),
+                });
+            }
+        }
+
+        ecs.commands().trigger(ComponentSyncEvent {
+            entity: target,
+            component_id: component.component_type,
+            data: nbt,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+
+    use super::*;
+    use crate::packet_decoder::write_varint;
+
+    fn build_entity_sync_packet(entity_id: i32, component_type: &str, nbt_bytes: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        write_varint(&mut data, entity_id);
+        write_varint(&mut data, component_type.len() as i32);
+        data.extend_from_slice(component_type.as_bytes());
+        data.extend_from_slice(&(nbt_bytes.len() as u16).to_be_bytes());
+        data.extend_from_slice(nbt_bytes);
+        data
+    }
+
+    #[test]
+    fn test_decode_cca_entity_sync_negative_array_length_falls_back_to_raw_nbt() {
+        // TAG_Int_Array (11) named "a" with a negative length prefix; every
+        // root framing parse_cca_component_nbt tries must fail cleanly
+        // rather than panic on the array length (the path this whole module
+        // feeds untrusted server bytes into unconditionally).
+        let mut nbt = vec![11, 0, 1, b'a'];
+        nbt.extend_from_slice(&(-1i32).to_be_bytes());
+
+        let data = build_entity_sync_packet(7, "test:comp", &nbt);
+
+        let packet = packet_decoder::decode_cca_entity_sync(&data).unwrap();
+        assert_eq!(packet.entity_id, 7);
+        assert_eq!(packet.components.len(), 1);
+        assert!(matches!(
+            packet.components[0].data,
+            ComponentData::Nbt(ref bytes) if bytes == &nbt
+        ));
+    }
+
+    #[test]
+    fn test_decode_cca_entity_sync_rejects_truncated_payload() {
+        // claims a 5-byte component type string but provides none
+        let mut data = Vec::new();
+        write_varint(&mut data, 1);
+        write_varint(&mut data, 5);
+
+        assert!(packet_decoder::decode_cca_entity_sync(&data).is_err());
+    }
+
+    #[test]
+    fn test_handle_cca_entity_sync_unknown_entity_does_not_panic() {
+        // no EntityIdIndex resource registered at all (as if the world
+        // hasn't reached the play phase yet); this must bail out quietly
+        // rather than panic on the missing resource or an unresolved id
+        let mut world = World::new();
+        let player = world.spawn_empty().id();
+
+        let data = build_entity_sync_packet(42, "test:comp", &[]);
+        handle_cca_entity_sync(&mut world, player, &data);
+    }
+}
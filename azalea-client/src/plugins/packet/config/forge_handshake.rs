@@ -0,0 +1,244 @@
+//! An opt-in subsystem that drives the Forge/FML handshake over a
+//! config-phase custom payload channel.
+//!
+//! Servers running Forge-family mod loaders negotiate a mod list and
+//! registry data over a dedicated plugin channel before the config phase can
+//! finish; a client that doesn't speak it gets stuck or kicked. This plugin
+//! registers that channel with [`PluginChannelRegistry`] and drives the
+//! handshake state machine to completion.
+
+use std::io::{Cursor, Read};
+
+use azalea_protocol::packets::config::ServerboundCustomPayload;
+use azalea_registry::identifier::Identifier;
+use bevy_app::{App, Plugin, PreStartup};
+use bevy_ecs::prelude::*;
+use tracing::{debug, warn};
+
+use super::{SendConfigPacketEvent, plugin_channels::PluginChannelRegistry};
+
+const FML_HANDSHAKE_CHANNEL: &str = "fml:handshake";
+
+const DISCRIMINANT_SERVER_HELLO: u8 = 0;
+const DISCRIMINANT_CLIENT_HELLO: u8 = 1;
+const DISCRIMINANT_MOD_LIST: u8 = 2;
+const DISCRIMINANT_REGISTRY_DATA: u8 = 3;
+const DISCRIMINANT_HANDSHAKE_ACK: u8 = 255;
+
+/// Registers the `fml:handshake` channel and drives the FML handshake state
+/// machine for any server that speaks it.
+///
+/// Not added by default; join a `Plugin` group with this only for servers
+/// you know are Forge/NeoForge-based, since handling the channel is
+/// meaningless (and harmless) against vanilla/Fabric servers. The channel is
+/// not advertised in `minecraft:register`.
+pub struct ForgeHandshakePlugin;
+
+impl Plugin for ForgeHandshakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PluginChannelRegistry>()
+            .add_systems(PreStartup, register_forge_channel);
+    }
+}
+
+fn register_forge_channel(mut registry: ResMut<PluginChannelRegistry>) {
+    registry.register(
+        Identifier::new(FML_HANDSHAKE_CHANNEL),
+        // We don't advertise this in `minecraft:register`; Forge's own
+        // channel negotiation (not plain Fabric-style registration) is what
+        // tells the server we can handshake.
+        false,
+        handle_forge_handshake_payload,
+    );
+}
+
+/// The current phase of the FML handshake for a player, tracked so
+/// `HandshakeAck` replies can echo the next phase.
+#[derive(Component, Debug, Default)]
+pub struct ForgeHandshakeState {
+    pub phase: u8,
+}
+
+/// The server's mod list, exposed once the `ModList` sub-packet has been
+/// received, so downstream code can inspect which mods are present.
+#[derive(Component, Debug, Default, Clone)]
+pub struct ForgeServerModList {
+    pub mods: Vec<(String, String)>,
+}
+
+fn handle_forge_handshake_payload(ecs: &mut World, player: Entity, data: &[u8]) {
+    let mut cursor = Cursor::new(data);
+    let Some(discriminant) = read_u8(&mut cursor) else {
+        warn!("Got empty FML handshake sub-packet");
+        return;
+    };
+
+    match discriminant {
+        DISCRIMINANT_SERVER_HELLO => {
+            let Some(server_fml_version) = read_u8(&mut cursor) else {
+                return;
+            };
+            debug!("Got FML ServerHello (protocol version {server_fml_version})");
+
+            ecs.entity_mut(player)
+                .insert(ForgeHandshakeState { phase: 0 });
+            send_client_hello(ecs, player, server_fml_version);
+            send_mod_list(ecs, player);
+        }
+        DISCRIMINANT_MOD_LIST => {
+            let Some(mods) = read_mod_list(&mut cursor) else {
+                warn!("Failed to decode FML ModList");
+                return;
+            };
+            debug!("Got FML ModList with {} mods", mods.len());
+            ecs.entity_mut(player)
+                .insert(ForgeServerModList { mods });
+        }
+        DISCRIMINANT_REGISTRY_DATA => {
+            debug!(
+                "Got FML RegistryData ({} bytes)",
+                data.len() - cursor.position() as usize
+            );
+            // We don't need the registry contents ourselves; azalea's own
+            // registry-data packets (handled in `registry_data`) already
+            // cover what we need to play along.
+        }
+        DISCRIMINANT_HANDSHAKE_ACK => {
+            let Some(phase) = read_u8(&mut cursor) else {
+                return;
+            };
+            debug!("Got FML HandshakeAck phase {phase}");
+            let next_phase = phase.wrapping_add(1);
+            ecs.entity_mut(player)
+                .insert(ForgeHandshakeState { phase: next_phase });
+            send_handshake_ack(ecs, player, next_phase);
+        }
+        other => {
+            warn!("Got unknown FML handshake sub-packet discriminant {other}");
+        }
+    }
+}
+
+fn send_client_hello(ecs: &mut World, player: Entity, server_fml_version: u8) {
+    let mut payload = vec![DISCRIMINANT_CLIENT_HELLO, server_fml_version];
+    send_fml_payload(ecs, player, &mut payload);
+}
+
+fn send_mod_list(ecs: &mut World, player: Entity) {
+    let mut payload = vec![DISCRIMINANT_MOD_LIST];
+    // We're a bot, not a modded client, so we advertise an empty mod list.
+    crate::packet_decoder::write_varint(&mut payload, 0);
+    send_fml_payload(ecs, player, &mut payload);
+}
+
+fn send_handshake_ack(ecs: &mut World, player: Entity, phase: u8) {
+    let mut payload = vec![DISCRIMINANT_HANDSHAKE_ACK, phase];
+    send_fml_payload(ecs, player, &mut payload);
+}
+
+fn send_fml_payload(ecs: &mut World, player: Entity, payload: &mut Vec<u8>) {
+    ecs.commands().trigger(SendConfigPacketEvent::new(
+        player,
+        ServerboundCustomPayload {
+            identifier: Identifier::new(FML_HANDSHAKE_CHANNEL),
+            data: std::mem::take(payload).into(),
+        },
+    ));
+}
+
+fn read_u8(cursor: &mut Cursor<&[u8]>) -> Option<u8> {
+    let mut byte = [0u8; 1];
+    cursor.read_exact(&mut byte).ok()?;
+    Some(byte[0])
+}
+
+fn read_mod_list(cursor: &mut Cursor<&[u8]>) -> Option<Vec<(String, String)>> {
+    let count = crate::packet_decoder::read_varint(cursor)?.max(0) as usize;
+
+    // `count` is an attacker-controlled VarInt; a server claiming on the
+    // order of 2^31 mods would make `Vec::with_capacity` request an
+    // allocation large enough to abort the process via `handle_alloc_error`
+    // rather than something we could catch as a normal error. Each entry
+    // needs at least a couple of bytes (a VarShort length prefix apiece), so
+    // there can never be more real entries than remaining bytes; cap the
+    // capacity hint there instead of trusting `count` directly.
+    let remaining = cursor.get_ref().len().saturating_sub(cursor.position() as usize);
+    let mut mods = Vec::with_capacity(count.min(remaining));
+    for _ in 0..count {
+        let modid = read_varshort_string(cursor)?;
+        let version = read_varshort_string(cursor)?;
+        mods.push((modid, version));
+    }
+    Some(mods)
+}
+
+fn read_varshort_string(cursor: &mut Cursor<&[u8]>) -> Option<String> {
+    let len = crate::packet_decoder::read_varshort(cursor)? as usize;
+    let mut bytes = vec![0u8; len];
+    cursor.read_exact(&mut bytes).ok()?;
+    crate::packet_decoder::decode_modified_utf8(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_varshort_string(out: &mut Vec<u8>, s: &str) {
+        let bytes = crate::packet_decoder::encode_modified_utf8(s);
+        out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        out.extend_from_slice(&bytes);
+    }
+
+    #[test]
+    fn test_read_varshort_string_decodes_modified_utf8() {
+        let mut data = Vec::new();
+        push_varshort_string(&mut data, "exämplemod");
+
+        let mut cursor = Cursor::new(data.as_slice());
+        assert_eq!(
+            read_varshort_string(&mut cursor),
+            Some("exämplemod".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read_mod_list_decodes_each_modid_version_pair() {
+        let mut data = Vec::new();
+        crate::packet_decoder::write_varint(&mut data, 2);
+        push_varshort_string(&mut data, "examplemod");
+        push_varshort_string(&mut data, "1.0.0");
+        push_varshort_string(&mut data, "other");
+        push_varshort_string(&mut data, "2.3");
+
+        let mut cursor = Cursor::new(data.as_slice());
+        let mods = read_mod_list(&mut cursor).unwrap();
+
+        assert_eq!(
+            mods,
+            vec![
+                ("examplemod".to_string(), "1.0.0".to_string()),
+                ("other".to_string(), "2.3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_mod_list_empty() {
+        let mut data = Vec::new();
+        crate::packet_decoder::write_varint(&mut data, 0);
+
+        let mut cursor = Cursor::new(data.as_slice());
+        assert_eq!(read_mod_list(&mut cursor), Some(vec![]));
+    }
+
+    #[test]
+    fn test_read_mod_list_huge_count_does_not_abort_on_allocation() {
+        // a count far larger than the actual (tiny) remaining buffer must not
+        // be used directly as a Vec capacity
+        let mut data = Vec::new();
+        crate::packet_decoder::write_varint(&mut data, i32::MAX);
+
+        let mut cursor = Cursor::new(data.as_slice());
+        assert_eq!(read_mod_list(&mut cursor), None);
+    }
+}
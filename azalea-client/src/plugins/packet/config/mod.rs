@@ -1,4 +1,8 @@
+mod cardinal_components;
 mod events;
+mod forge_handshake;
+mod plugin_channels;
+mod resource_packs;
 
 use std::io::Cursor;
 
@@ -7,8 +11,13 @@ use azalea_protocol::{
     packets::{ConnectionProtocol, config::*},
     read::{ReadPacketError, deserialize_packet},
 };
+use azalea_registry::identifier::Identifier;
 use bevy_ecs::prelude::*;
+pub use cardinal_components::*;
 pub use events::*;
+pub use forge_handshake::*;
+pub use plugin_channels::*;
+pub use resource_packs::*;
 use tracing::{debug, warn};
 
 use super::{as_system, declare_packet_handlers};
@@ -78,78 +87,45 @@ impl ConfigPacketHandler<'_> {
                 .registries
                 .append(p.registry_id.clone(), p.entries.clone());
         });
+
+        // Modded servers can append extra block kinds to the block registry
+        // (or ship whole new block registries), which pushes valid block
+        // state ids above vanilla's MAX_STATE. Widen the accepted range to
+        // match, instead of requiring the user to guess and call
+        // `set_mod_max_state` by hand.
+        maybe_widen_mod_max_state(&p.registry_id, p.entries.len());
     }
 
     pub fn custom_payload(&mut self, p: &ClientboundCustomPayload) {
         let channel_name = p.identifier.to_string();
-        tracing::info!("CUSTOM PAYLOAD RECEIVED on channel: {}", channel_name);
+        debug!("Got custom payload packet on channel {channel_name}");
 
-        // Handle Fabric API registry sync synchronously to avoid being disconnected
-        // before we can respond.
-        if channel_name == "fabric:registry/sync" {
-            tracing::info!(
-                "Fabric registry sync received ({} bytes), sending completion acknowledgment",
-                p.data.len()
-            );
-            use azalea_registry::identifier::Identifier;
-            self.ecs
-                .commands()
-                .trigger(SendConfigPacketEvent::new(
-                    self.player,
-                    ServerboundCustomPayload {
-                        identifier: Identifier::new("fabric:registry/sync/complete"),
-                        data: vec![].into(),
-                    },
-                ));
-            tracing::info!("Fabric registry sync completion sent");
-        }
+        ensure_builtin_channels_registered(self.ecs);
 
-        // Handle Cardinal Components API entity sync packets.
-        // We just acknowledge receipt by doing nothing - the bot doesn't need component data.
-        if channel_name == "cardinal-components:entity_sync" {
-            tracing::info!(
-                "CONFIG: Received cardinal-components:entity_sync ({} bytes), reading packet data",
-                p.data.len()
+        // `minecraft:register` is handled here rather than through the
+        // registry, since its reply is built from every advertised channel
+        // rather than dispatched to a single one.
+        if channel_name == "minecraft:register" {
+            as_system::<(Commands, Res<PluginChannelRegistry>)>(
+                self.ecs,
+                |(mut commands, registry)| {
+                    commands.trigger(SendConfigPacketEvent::new(
+                        self.player,
+                        ServerboundCustomPayload {
+                            identifier: Identifier::new("minecraft:register"),
+                            data: registry.register_payload().into(),
+                        },
+                    ));
+                },
             );
-            // Try to read the packet data to show we can handle it
-            // Format: entity_id (varint) + component_data
-            if p.data.len() >= 1 {
-                tracing::info!(
-                    "  Packet data (first 32 bytes): {:?}",
-                    &p.data[..p.data.len().min(32)]
-                );
-            }
+            return;
         }
 
-        // When we receive minecraft:register, we need to respond by registering
-        // the Fabric API channels so the server knows we support them.
-        if channel_name == "minecraft:register" {
-            tracing::info!("Received minecraft:register, registering Fabric and CCA channels");
-            use azalea_registry::identifier::Identifier;
-            // Send minecraft:register back with the Fabric channels we support
-            // The payload is a list of null-terminated strings
-            let mut payload = Vec::new();
-            payload.extend_from_slice(b"fabric:registry/sync\0");
-            payload.extend_from_slice(b"fabric:registry/sync/complete\0");
-            // Register Cardinal Components API channels for mods like Traveler's Backpack
-            // This must be done in config phase so the server knows we support CCA
-            payload.extend_from_slice(b"cardinal-components:entity_sync\0");
-            payload.extend_from_slice(b"cardinal-components:block_sync\0");
-            payload.extend_from_slice(b"cardinal-components:chunk_sync\0");
-            payload.extend_from_slice(b"cardinal-components:world_sync\0");
-            self.ecs
-                .commands()
-                .trigger(SendConfigPacketEvent::new(
-                    self.player,
-                    ServerboundCustomPayload {
-                        identifier: Identifier::new("minecraft:register"),
-                        data: payload.into(),
-                    },
-                ));
-            tracing::info!("Registered Fabric and CCA channels with server");
+        if PluginChannelRegistry::dispatch(self.ecs, self.player, &p.identifier, &p.data) {
+            return;
         }
 
-        // Also emit event for FabricHandshakePlugin to handle c:version/c:register
+        // Fall back to the generic event for unregistered channels.
         as_system::<MessageWriter<_>>(self.ecs, |mut events| {
             events.write(ReceiveConfigPacketEvent {
                 entity: self.player,
@@ -238,10 +214,14 @@ impl ConfigPacketHandler<'_> {
                 prompt: p.prompt.to_owned(),
             });
         });
+
+        resource_packs::handle_resource_pack_push(self.ecs, self.player, p);
     }
 
     pub fn resource_pack_pop(&mut self, p: &ClientboundResourcePackPop) {
         debug!("Got resource pack pop packet {p:?}");
+
+        resource_packs::handle_resource_pack_pop(self.ecs, self.player, p);
     }
 
     pub fn update_enabled_features(&mut self, p: &ClientboundUpdateEnabledFeatures) {
@@ -283,13 +263,17 @@ impl ConfigPacketHandler<'_> {
     pub fn select_known_packs(&mut self, p: &ClientboundSelectKnownPacks) {
         debug!("Got select known packs packet {p:?}");
 
+        // Echo back the server's offered known packs so datapack-gated
+        // registry data is sent correctly, instead of always claiming we
+        // know none of them.
+        let known_packs = p.known_packs.clone();
         as_system::<Commands>(self.ecs, |mut commands| {
-            // resource pack management isn't implemented
+            commands
+                .entity(self.player)
+                .insert(KnownPacks(known_packs.clone()));
             commands.trigger(SendConfigPacketEvent::new(
                 self.player,
-                ServerboundSelectKnownPacks {
-                    known_packs: vec![],
-                },
+                ServerboundSelectKnownPacks { known_packs },
             ));
         });
     }
@@ -312,3 +296,139 @@ impl ConfigPacketHandler<'_> {
         debug!("Got code of conduct packet {p:?}");
     }
 }
+
+/// Whether `registry_id` is a block registry (vanilla's `minecraft:block`,
+/// or a modded block registry appended alongside it, e.g. `forge:block`).
+fn is_block_registry(registry_id: &Identifier) -> bool {
+    let name = registry_id.to_string();
+    name == "minecraft:block" || name.ends_with(":block")
+}
+
+/// Widens [`azalea_block::range`]'s modded block-state range from a decoded
+/// registry's entry count, if `registry_id` names a block registry and the
+/// implied max exceeds the current one.
+///
+/// Never shrinks a previously widened max: a later, smaller block registry
+/// (or one sent again with fewer entries) must not undo an earlier, larger
+/// one still in effect.
+fn maybe_widen_mod_max_state(registry_id: &Identifier, entry_count: usize) {
+    if !is_block_registry(registry_id) {
+        return;
+    }
+
+    let implied_max_state = azalea_block::range::mod_max_state_for_entry_count(entry_count);
+    if implied_max_state > azalea_block::range::get_mod_max_state() {
+        debug!("Widening modded block state range to {implied_max_state} from registry {registry_id}");
+        azalea_block::range::set_mod_max_state(implied_max_state);
+    }
+}
+
+/// Marker resource recording that [`ensure_builtin_channels_registered`] has
+/// already run, so it can tell "built-ins registered" apart from "some
+/// plugin (e.g. [`ForgeHandshakePlugin`](super::ForgeHandshakePlugin))
+/// already created the [`PluginChannelRegistry`] resource for its own
+/// channels".
+#[derive(Resource, Default)]
+struct BuiltinChannelsRegistered;
+
+/// Registers the channels the core crate used to hardcode in
+/// `custom_payload` directly on [`PluginChannelRegistry`], the first time
+/// we see a custom payload packet.
+///
+/// This keeps default behavior (advertising and acking Fabric API's registry
+/// sync, and advertising the Cardinal Components channels) unchanged while
+/// letting any plugin add to it by registering its own channels. A handler a
+/// plugin registered earlier for one of these same built-in channels is
+/// replaced by the built-in one here.
+fn ensure_builtin_channels_registered(ecs: &mut World) {
+    if ecs.contains_resource::<BuiltinChannelsRegistered>() {
+        return;
+    }
+    ecs.insert_resource(BuiltinChannelsRegistered);
+
+    // `PluginChannelRegistry` may already exist if a plugin (e.g.
+    // `ForgeHandshakePlugin`) created it in `build()` to register its own
+    // channels before the first packet ever arrived; reuse it instead of
+    // clobbering it so ordering between plugins doesn't matter.
+    let mut registry = ecs.remove_resource::<PluginChannelRegistry>().unwrap_or_default();
+
+    // Fabric API registry sync must be acked during the config phase to
+    // avoid being disconnected before we can respond.
+    registry.register(
+        Identifier::new("fabric:registry/sync"),
+        true,
+        |ecs, player, data| {
+            debug!(
+                "Fabric registry sync received ({} bytes), sending completion acknowledgment",
+                data.len()
+            );
+            ecs.commands().trigger(SendConfigPacketEvent::new(
+                player,
+                ServerboundCustomPayload {
+                    identifier: Identifier::new("fabric:registry/sync/complete"),
+                    data: vec![].into(),
+                },
+            ));
+        },
+    );
+    registry.register(Identifier::new("fabric:registry/sync/complete"), true, |_, _, _| {});
+
+    // Cardinal Components API channels, so mods like Traveler's Backpack
+    // know we support component sync.
+    registry.register(
+        Identifier::new("cardinal-components:entity_sync"),
+        true,
+        cardinal_components::handle_cca_entity_sync,
+    );
+    registry.register(Identifier::new("cardinal-components:block_sync"), true, |_, _, _| {});
+    registry.register(Identifier::new("cardinal-components:chunk_sync"), true, |_, _, _| {});
+    registry.register(Identifier::new("cardinal-components:world_sync"), true, |_, _, _| {});
+
+    ecs.insert_resource(registry);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // `MOD_MAX_STATE` is a process-wide static in `azalea_block::range`;
+    // serialize the tests that touch it so they can't race each other.
+    static MOD_MAX_STATE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_is_block_registry_matches_core_and_modded_block_registries() {
+        assert!(is_block_registry(&Identifier::new("minecraft:block")));
+        assert!(is_block_registry(&Identifier::new("examplemod:block")));
+        assert!(!is_block_registry(&Identifier::new("minecraft:item")));
+        assert!(!is_block_registry(&Identifier::new(
+            "minecraft:block_entity_type"
+        )));
+    }
+
+    #[test]
+    fn test_maybe_widen_mod_max_state_ignores_non_block_registry() {
+        let _guard = MOD_MAX_STATE_TEST_LOCK.lock().unwrap();
+        azalea_block::range::set_mod_max_state(0);
+
+        maybe_widen_mod_max_state(&Identifier::new("minecraft:item"), 1_000_000);
+
+        assert_eq!(azalea_block::range::get_mod_max_state(), 0);
+    }
+
+    #[test]
+    fn test_maybe_widen_mod_max_state_does_not_shrink_previously_widened_max() {
+        let _guard = MOD_MAX_STATE_TEST_LOCK.lock().unwrap();
+        azalea_block::range::set_mod_max_state(0);
+
+        maybe_widen_mod_max_state(&Identifier::new("minecraft:block"), 1_000);
+        let widened = azalea_block::range::get_mod_max_state();
+        assert!(widened > 0);
+
+        // a later registry with fewer entries must not shrink the range
+        // back down
+        maybe_widen_mod_max_state(&Identifier::new("minecraft:block"), 1);
+        assert_eq!(azalea_block::range::get_mod_max_state(), widened);
+    }
+}
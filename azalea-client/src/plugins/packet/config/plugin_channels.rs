@@ -0,0 +1,156 @@
+//! A pluggable registry for config-phase plugin channels.
+//!
+//! Historically [`super::ConfigPacketHandler::custom_payload`] special-cased
+//! every mod-support channel it knew about (Fabric API's registry sync,
+//! Cardinal Components, `minecraft:register`) directly in the handler body.
+//! That doesn't scale: every new mod-loader integration had to edit the core
+//! packet handler. [`PluginChannelRegistry`] lets plugins register a channel
+//! [`Identifier`] plus a handler up front, so `custom_payload` only needs to
+//! dispatch.
+
+use std::collections::HashMap;
+
+use azalea_registry::identifier::Identifier;
+use bevy_ecs::prelude::*;
+
+/// A handler invoked with the raw payload bytes whenever a custom-payload
+/// packet arrives on its registered channel.
+pub type PluginChannelHandler = Box<dyn Fn(&mut World, Entity, &[u8]) + Send + Sync>;
+
+struct PluginChannelEntry {
+    /// Whether this channel's name should be included in the
+    /// `minecraft:register` reply we send the server.
+    advertise: bool,
+    handler: PluginChannelHandler,
+}
+
+/// Resource holding every config-phase plugin channel a mod-support plugin
+/// has registered.
+///
+/// Plugins register their channels (usually from their `Plugin::build`) with
+/// [`PluginChannelRegistry::register`]. `custom_payload` then builds the
+/// `minecraft:register` reply from every advertised channel and dispatches
+/// incoming payloads to the matching handler, instead of hardcoding channel
+/// names.
+#[derive(Default, Resource)]
+pub struct PluginChannelRegistry {
+    channels: HashMap<Identifier, PluginChannelEntry>,
+}
+
+impl PluginChannelRegistry {
+    /// Registers a handler for `channel`.
+    ///
+    /// If `advertise` is true, `channel` is included in the
+    /// `minecraft:register` reply so the server knows we support it.
+    pub fn register(
+        &mut self,
+        channel: Identifier,
+        advertise: bool,
+        handler: impl Fn(&mut World, Entity, &[u8]) + Send + Sync + 'static,
+    ) {
+        self.channels.insert(
+            channel,
+            PluginChannelEntry {
+                advertise,
+                handler: Box::new(handler),
+            },
+        );
+    }
+
+    /// Builds the null-terminated channel list for a `minecraft:register`
+    /// reply, in the format Fabric/Forge clients send.
+    pub fn register_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for identifier in self
+            .channels
+            .iter()
+            .filter(|(_, entry)| entry.advertise)
+            .map(|(identifier, _)| identifier)
+        {
+            payload.extend_from_slice(identifier.to_string().as_bytes());
+            payload.push(0);
+        }
+        payload
+    }
+
+    /// Dispatches `data` to the handler registered for `channel`, if any.
+    ///
+    /// Returns whether a handler was found and run. The registry is
+    /// temporarily removed from `ecs` for the duration of the call so the
+    /// handler can take `&mut World` without a double-borrow.
+    pub fn dispatch(ecs: &mut World, player: Entity, channel: &Identifier, data: &[u8]) -> bool {
+        let Some(mut registry) = ecs.remove_resource::<PluginChannelRegistry>() else {
+            return false;
+        };
+
+        let handled = if let Some(entry) = registry.channels.get(channel) {
+            (entry.handler)(ecs, player, data);
+            true
+        } else {
+            false
+        };
+
+        ecs.insert_resource(registry);
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_register_payload_includes_only_advertised_channels() {
+        let mut registry = PluginChannelRegistry::default();
+        registry.register(Identifier::new("test:advertised"), true, |_, _, _| {});
+        registry.register(Identifier::new("test:hidden"), false, |_, _, _| {});
+
+        let payload = String::from_utf8(registry.register_payload()).unwrap();
+
+        assert!(payload.contains("test:advertised\0"));
+        assert!(!payload.contains("test:hidden"));
+    }
+
+    #[test]
+    fn test_dispatch_runs_registered_handler_and_reports_found() {
+        let mut world = World::new();
+        let player = world.spawn_empty().id();
+
+        let received = Arc::new(Mutex::new(None));
+        let received_in_handler = received.clone();
+
+        let mut registry = PluginChannelRegistry::default();
+        registry.register(Identifier::new("test:channel"), true, move |_, _, data| {
+            *received_in_handler.lock().unwrap() = Some(data.to_vec());
+        });
+        world.insert_resource(registry);
+
+        let handled = PluginChannelRegistry::dispatch(
+            &mut world,
+            player,
+            &Identifier::new("test:channel"),
+            b"hello",
+        );
+
+        assert!(handled);
+        assert_eq!(*received.lock().unwrap(), Some(b"hello".to_vec()));
+    }
+
+    #[test]
+    fn test_dispatch_unregistered_channel_returns_false() {
+        let mut world = World::new();
+        let player = world.spawn_empty().id();
+        world.insert_resource(PluginChannelRegistry::default());
+
+        let handled = PluginChannelRegistry::dispatch(
+            &mut world,
+            player,
+            &Identifier::new("test:unknown"),
+            b"",
+        );
+
+        assert!(!handled);
+    }
+}
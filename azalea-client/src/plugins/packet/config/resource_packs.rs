@@ -0,0 +1,386 @@
+//! Resource-pack and known-packs negotiation.
+//!
+//! `select_known_packs` used to always reply with an empty list (so
+//! datapack-gated registry data could come back wrong), and
+//! `resource_pack_push`/`resource_pack_pop` only emitted an event or logged,
+//! so the bot never actually participated in pack negotiation the way a
+//! real client does. This echoes the server's offered known packs, and
+//! drives an opt-in download + SHA-1 verify + disk cache pipeline for pushed
+//! resource packs, replying with the real `ServerboundResourcePack` status
+//! sequence instead of silently doing nothing.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use azalea_protocol::packets::config::{
+    ClientboundResourcePackPop, ClientboundResourcePackPush, KnownPack, ServerboundResourcePack,
+    ServerboundResourcePackAction,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use sha1::{Digest, Sha1};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::SendConfigPacketEvent;
+
+/// Whether the bot actually downloads pushed resource packs, or just
+/// declines/acknowledges them. Headless bots usually don't need pack
+/// contents, so this defaults to off.
+#[derive(Resource, Clone, Debug)]
+pub struct ResourcePackConfig {
+    pub download_enabled: bool,
+    pub cache_dir: PathBuf,
+}
+
+impl Default for ResourcePackConfig {
+    fn default() -> Self {
+        Self {
+            download_enabled: false,
+            cache_dir: PathBuf::from("resourcepacks"),
+        }
+    }
+}
+
+/// The resource packs currently pushed to a player and where they've been
+/// cached on disk, keyed by pack id.
+#[derive(Component, Default, Debug, Clone)]
+pub struct ResourcePackState {
+    pub packs: HashMap<Uuid, PathBuf>,
+}
+
+/// The known packs a player last echoed back to the server in response to
+/// `select_known_packs`, so datapack-gated registry data resolves correctly.
+#[derive(Component, Default, Debug, Clone)]
+pub struct KnownPacks(pub Vec<KnownPack>);
+
+/// A resource-pack download kicked off by [`handle_resource_pack_push`],
+/// still running on its background thread.
+struct PendingResourcePackDownload {
+    player: Entity,
+    id: Uuid,
+    rx: mpsc::Receiver<Result<PathBuf, String>>,
+}
+
+/// Resource-pack downloads currently in flight, completed by
+/// [`poll_resource_pack_downloads`] once their background thread finishes.
+#[derive(Resource, Default)]
+struct PendingResourcePackDownloads(Vec<PendingResourcePackDownload>);
+
+/// Polls [`PendingResourcePackDownloads`] and completes each one (caching
+/// it, updating [`ResourcePackState`], and replying with the real status
+/// sequence) as soon as its background thread finishes.
+///
+/// Not a blocking wait like `fetch_with_timeout`'s inner `recv_timeout`:
+/// this only ever does a non-blocking `try_recv`, so a slow/stalling host
+/// stalls only its own pending download, not packet processing for this
+/// connection (keep-alives included).
+fn poll_resource_pack_downloads(world: &mut World) {
+    let Some(mut pending) = world.get_resource_mut::<PendingResourcePackDownloads>() else {
+        return;
+    };
+    if pending.0.is_empty() {
+        return;
+    }
+
+    let mut finished = Vec::new();
+    pending.0.retain_mut(|download| match download.rx.try_recv() {
+        Ok(result) => {
+            finished.push((download.player, download.id, result));
+            false
+        }
+        Err(mpsc::TryRecvError::Empty) => true,
+        Err(mpsc::TryRecvError::Disconnected) => {
+            finished.push((
+                download.player,
+                download.id,
+                Err("download thread disconnected without a result".to_string()),
+            ));
+            false
+        }
+    });
+    drop(pending);
+
+    for (player, id, result) in finished {
+        match result {
+            Ok(path) => {
+                debug!("Resource pack {id} downloaded and verified to {path:?}");
+                insert_pack(world, player, id, path);
+                send_status(world, player, id, ServerboundResourcePackAction::Downloaded);
+                send_status(
+                    world,
+                    player,
+                    id,
+                    ServerboundResourcePackAction::SuccessfullyLoaded,
+                );
+            }
+            Err(err) => {
+                warn!("Failed to download resource pack {id}: {err}");
+                send_status(
+                    world,
+                    player,
+                    id,
+                    ServerboundResourcePackAction::FailedDownload,
+                );
+            }
+        }
+    }
+}
+
+/// Polls and completes resource-pack downloads kicked off by
+/// [`handle_resource_pack_push`] on a tick rather than blocking the
+/// packet-processing thread on them.
+///
+/// Not added by default; opt in alongside setting
+/// [`ResourcePackConfig::download_enabled`].
+pub struct ResourcePackPlugin;
+
+impl Plugin for ResourcePackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingResourcePackDownloads>()
+            .add_systems(Update, poll_resource_pack_downloads);
+    }
+}
+
+/// Handles a pushed resource pack: declines it outright if downloads are
+/// disabled, otherwise accepts it and kicks the download+verify off on a
+/// background thread, returning immediately. [`poll_resource_pack_downloads`]
+/// picks up the result and sends the remaining status replies once it's
+/// ready, so a slow/stalling host can't block this connection's packet
+/// processing on the download.
+pub fn handle_resource_pack_push(ecs: &mut World, player: Entity, p: &ClientboundResourcePackPush) {
+    let config = ecs
+        .get_resource::<ResourcePackConfig>()
+        .cloned()
+        .unwrap_or_default();
+
+    if !config.download_enabled {
+        debug!("Resource pack downloads disabled, declining pack {}", p.id);
+        send_status(ecs, player, p.id, ServerboundResourcePackAction::Declined);
+        return;
+    }
+
+    send_status(ecs, player, p.id, ServerboundResourcePackAction::Accepted);
+
+    let id = p.id;
+    let url = p.url.clone();
+    let hash = p.hash.clone();
+    let cache_dir = config.cache_dir.clone();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(download_and_verify(&cache_dir, &url, &hash));
+    });
+
+    ecs.get_resource_or_insert_with(PendingResourcePackDownloads::default)
+        .0
+        .push(PendingResourcePackDownload { player, id, rx });
+}
+
+pub fn handle_resource_pack_pop(ecs: &mut World, player: Entity, p: &ClientboundResourcePackPop) {
+    let Some(mut state) = ecs.get_mut::<ResourcePackState>(player) else {
+        return;
+    };
+    match p.id {
+        Some(id) => {
+            state.packs.remove(&id);
+        }
+        None => state.packs.clear(),
+    }
+}
+
+fn insert_pack(ecs: &mut World, player: Entity, id: Uuid, path: PathBuf) {
+    let mut entity_mut = ecs.entity_mut(player);
+    if let Some(mut state) = entity_mut.get_mut::<ResourcePackState>() {
+        state.packs.insert(id, path);
+    } else {
+        entity_mut.insert(ResourcePackState {
+            packs: HashMap::from([(id, path)]),
+        });
+    }
+}
+
+fn send_status(ecs: &mut World, player: Entity, id: Uuid, action: ServerboundResourcePackAction) {
+    ecs.commands().trigger(SendConfigPacketEvent::new(
+        player,
+        ServerboundResourcePack { id, action },
+    ));
+}
+
+/// Downloads `url`, verifies it against the server-supplied SHA-1 `hash`
+/// (skipped when the server sent an empty hash), and caches it on disk keyed
+/// by the verified hash, returning the cached path. When the server sent a
+/// hash, an already-cached pack is served from disk without re-downloading;
+/// with an empty hash the pack is always downloaded again.
+fn download_and_verify(cache_dir: &Path, url: &str, expected_hash: &str) -> Result<PathBuf, String> {
+    if !expected_hash.is_empty() {
+        if !is_sha1_hex(expected_hash) {
+            return Err(format!("server sent a malformed resource pack hash: {expected_hash:?}"));
+        }
+
+        let cached = cache_dir.join(expected_hash);
+        if cached.exists() {
+            return Ok(cached);
+        }
+    }
+
+    let bytes = fetch_with_timeout(url)?;
+
+    let actual_hash = format!("{:x}", Sha1::digest(&bytes));
+    if !expected_hash.is_empty() && !actual_hash.eq_ignore_ascii_case(expected_hash) {
+        return Err(format!(
+            "hash mismatch: server said {expected_hash}, downloaded data hashes to {actual_hash}"
+        ));
+    }
+
+    fs::create_dir_all(cache_dir).map_err(|e| e.to_string())?;
+    let path = cache_dir.join(&actual_hash);
+    fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+const DOWNLOAD_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DOWNLOAD_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Downloads `url`'s body on its own background thread, bounded by
+/// [`DOWNLOAD_CONNECT_TIMEOUT`]/[`DOWNLOAD_READ_TIMEOUT`].
+///
+/// Called from the background thread `handle_resource_pack_push` already
+/// spawns to run [`download_and_verify`] off the packet-processing thread,
+/// so blocking here only blocks that worker; the agent-level timeouts bound
+/// the inner thread this spawns, and `recv_timeout` is a backstop against
+/// that inner thread hanging in a way the agent timeouts don't cover (e.g.
+/// a stuck DNS resolution).
+fn fetch_with_timeout(url: &str) -> Result<Vec<u8>, String> {
+    let url = url.to_string();
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let result = (|| -> Result<Vec<u8>, String> {
+            let agent = ureq::AgentBuilder::new()
+                .timeout_connect(DOWNLOAD_CONNECT_TIMEOUT)
+                .timeout_read(DOWNLOAD_READ_TIMEOUT)
+                .build();
+            let response = agent.get(&url).call().map_err(|e| e.to_string())?;
+            let mut bytes = Vec::new();
+            response
+                .into_reader()
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+            Ok(bytes)
+        })();
+        // the receiver may already be gone if we timed out below; that's fine
+        let _ = tx.send(result);
+    });
+
+    rx.recv_timeout(DOWNLOAD_CONNECT_TIMEOUT + DOWNLOAD_READ_TIMEOUT)
+        .map_err(|_| "resource pack download timed out".to_string())?
+}
+
+/// Whether `hash` looks like a SHA-1 hex digest (40 hex characters), rather
+/// than something else entirely.
+///
+/// The server-supplied hash gets joined onto `cache_dir` as a path segment,
+/// so this must be checked before that happens: an unvalidated hash (e.g.
+/// `../../etc/passwd`, or an absolute path) could otherwise be used to read
+/// or clobber an arbitrary file outside the cache directory.
+fn is_sha1_hex(hash: &str) -> bool {
+    hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sha1_hex_accepts_valid_digest() {
+        assert!(is_sha1_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+        assert!(is_sha1_hex("DA39A3EE5E6B4B0D3255BFEF95601890AFD80709"));
+    }
+
+    #[test]
+    fn test_is_sha1_hex_rejects_path_traversal_and_malformed_input() {
+        assert!(!is_sha1_hex("../../etc/passwd"));
+        assert!(!is_sha1_hex("/etc/passwd"));
+        assert!(!is_sha1_hex(""));
+        // one character short of a real digest
+        assert!(!is_sha1_hex("da39a3ee5e6b4b0d3255bfef95601890afd8070"));
+        // right length but contains a non-hex character
+        assert!(!is_sha1_hex("ga39a3ee5e6b4b0d3255bfef95601890afd80709"));
+    }
+
+    #[test]
+    fn test_poll_resource_pack_downloads_applies_completed_download() {
+        let mut world = World::new();
+        world.init_resource::<PendingResourcePackDownloads>();
+        let player = world.spawn_empty().id();
+
+        let id = Uuid::nil();
+        let path = PathBuf::from("resourcepacks/deadbeef");
+        let (tx, rx) = mpsc::channel();
+        tx.send(Ok(path.clone())).unwrap();
+
+        world
+            .resource_mut::<PendingResourcePackDownloads>()
+            .0
+            .push(PendingResourcePackDownload { player, id, rx });
+
+        poll_resource_pack_downloads(&mut world);
+
+        assert!(world.resource::<PendingResourcePackDownloads>().0.is_empty());
+        let state = world.get::<ResourcePackState>(player).unwrap();
+        assert_eq!(state.packs.get(&id), Some(&path));
+    }
+
+    #[test]
+    fn test_poll_resource_pack_downloads_leaves_unfinished_downloads_pending() {
+        let mut world = World::new();
+        world.init_resource::<PendingResourcePackDownloads>();
+        let player = world.spawn_empty().id();
+
+        let id = Uuid::nil();
+        let (_tx, rx) = mpsc::channel();
+
+        world
+            .resource_mut::<PendingResourcePackDownloads>()
+            .0
+            .push(PendingResourcePackDownload { player, id, rx });
+
+        poll_resource_pack_downloads(&mut world);
+
+        // the sender is still alive (held by `_tx`) but hasn't sent a result
+        // yet, so this download must still be waiting, not dropped or
+        // spuriously applied
+        assert_eq!(
+            world.resource::<PendingResourcePackDownloads>().0.len(),
+            1
+        );
+        assert!(world.get::<ResourcePackState>(player).is_none());
+    }
+
+    #[test]
+    fn test_poll_resource_pack_downloads_drops_disconnected_download() {
+        let mut world = World::new();
+        world.init_resource::<PendingResourcePackDownloads>();
+        let player = world.spawn_empty().id();
+
+        let id = Uuid::nil();
+        let (tx, rx) = mpsc::channel();
+        drop(tx); // the download thread died without ever sending a result
+
+        world
+            .resource_mut::<PendingResourcePackDownloads>()
+            .0
+            .push(PendingResourcePackDownload { player, id, rx });
+
+        poll_resource_pack_downloads(&mut world);
+
+        assert!(world.resource::<PendingResourcePackDownloads>().0.is_empty());
+        assert!(world.get::<ResourcePackState>(player).is_none());
+    }
+}